@@ -6,6 +6,12 @@
 //! - Load editor command from the `VISUAL` or `EDITOR` environment variables
 //! - Specify high-priority override and low-priority default commands to use
 //! - Pass one or more paths to be opened by the editor
+//! - Edit in-memory content via a round-trip through a temporary file
+//! - Load editor command from git's `GIT_EDITOR`/`core.editor` (with the
+//!   `git-config` feature)
+//! - Spawn the editor and wait for it with [EditorBuilder::run], with
+//!   optional terminal handoff for TUI apps (with the `terminal-handoff`
+//!   feature)
 //! - Flexible builder pattern
 //!
 //! ## Examples
@@ -65,6 +71,11 @@
 //! Command parsing is handled by the crate [shellish_parse] (with default
 //! [ParseOptions]). Refer to those docs for exact details on the syntax.
 //!
+//! If the command contains a placeholder token such as `{}`, paths are
+//! substituted into the command instead of being appended as trailing
+//! arguments. See [EditorBuilder::build_commands] for the full list of
+//! tokens.
+//!
 //! ## Lifetimes
 //!
 //! [EditorBuilder] accepts a lifetime parameter, which is bound to the string
@@ -97,10 +108,14 @@ use std::{
     borrow::Cow,
     env,
     error::Error,
+    ffi::OsString,
     fmt::{self, Display},
+    fs,
+    io::{self, Read, Write},
     path::Path,
-    process::Command,
+    process::{Command, ExitStatus},
 };
+use tempfile::Builder as TempFileBuilder;
 
 /// A builder for a [Command] that will open the user's configured editor. For
 /// simple cases you probably can just use [EditorBuilder::edit_file]. See
@@ -112,6 +127,199 @@ pub struct EditorBuilder<'a> {
     command: Option<Cow<'a, str>>,
     /// Path(s) to pass as the final argument(s) to the command
     paths: Vec<Cow<'a, Path>>,
+    /// File extension to use for the temporary file created by
+    /// [edit_content](Self::edit_content)/[build_and_edit](Self::build_and_edit),
+    /// so the editor can apply appropriate syntax highlighting
+    extension: Option<Cow<'a, str>>,
+    /// If no source is populated, should we fall back to scanning `PATH` for
+    /// a well-known editor? Resolved at [build](Self::build) time, not
+    /// immediately, since it requires filesystem access
+    use_fallbacks: bool,
+    /// Working directory to set on the produced [Command]
+    current_dir: Option<Cow<'a, Path>>,
+    /// Environment variables to set on the produced [Command]
+    envs: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    /// Should [run](Self::run) leave raw mode/the alternate screen before
+    /// spawning the editor, and restore it afterward?
+    #[cfg(feature = "terminal-handoff")]
+    terminal_handoff: bool,
+}
+
+/// Well-known editor commands to probe for on `PATH` when
+/// [fallbacks](EditorBuilder::fallbacks) is enabled and no other source is
+/// populated. Ordered from most to least preferred.
+#[cfg(unix)]
+const FALLBACK_CANDIDATES: &[&str] = &["nano", "vim", "vi", "emacs", "micro"];
+#[cfg(windows)]
+const FALLBACK_CANDIDATES: &[&str] = &["notepad.exe"];
+#[cfg(not(any(unix, windows)))]
+const FALLBACK_CANDIDATES: &[&str] = &[];
+
+/// Check whether `program` can be found as an executable file in one of the
+/// directories listed in `PATH`. On Windows, also tries each extension listed
+/// in `PATHEXT`, since executables there are commonly referenced without
+/// their extension (e.g. `notepad` for `notepad.exe`). On Unix, a regular
+/// file that isn't executable (missing all of the `u+x`/`g+x`/`o+x` bits)
+/// doesn't count as found.
+fn find_on_path(program: &str) -> bool {
+    let Some(path_var) = env::var_os("PATH") else {
+        return false;
+    };
+
+    #[cfg(windows)]
+    let extensions: Vec<String> = env::var("PATHEXT")
+        .unwrap_or_default()
+        .split(';')
+        .map(|ext| ext.to_lowercase())
+        .collect();
+
+    env::split_paths(&path_var).any(|dir| {
+        #[cfg(windows)]
+        {
+            if Path::new(program).extension().is_some() {
+                return dir.join(program).is_file();
+            }
+            extensions
+                .iter()
+                .any(|ext| dir.join(format!("{program}{ext}")).is_file())
+        }
+        #[cfg(unix)]
+        {
+            is_executable_file(&dir.join(program))
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            dir.join(program).is_file()
+        }
+    })
+}
+
+/// Check whether `path` is a regular file with at least one executable bit
+/// set
+#[cfg(unix)]
+fn is_executable_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path)
+        .map(|metadata| {
+            metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+        })
+        .unwrap_or(false)
+}
+
+/// Read git's `core.editor` config value by shelling out to `git config
+/// --get core.editor`. Returns `None` if `git` isn't on `PATH`, the config
+/// value isn't set, or the value is empty - never an error, since an
+/// unpopulated source should just let the chain continue.
+#[cfg(feature = "git-config")]
+fn git_config_core_editor() -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["config", "--get", "core.editor"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8(output.stdout).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+/// RAII guard used by [EditorBuilder::run] to hand the terminal off to the
+/// editor process. Leaves raw mode and the alternate screen on creation, and
+/// restores them on drop - including when the caller unwinds through a panic
+/// or the editor fails to spawn - so the terminal is never left in a broken
+/// state.
+#[cfg(feature = "terminal-handoff")]
+struct TerminalHandoffGuard;
+
+#[cfg(feature = "terminal-handoff")]
+impl TerminalHandoffGuard {
+    fn enter() -> io::Result<Self> {
+        crossterm::terminal::disable_raw_mode()?;
+        crossterm::execute!(
+            io::stdout(),
+            crossterm::terminal::LeaveAlternateScreen
+        )?;
+        Ok(Self)
+    }
+}
+
+#[cfg(feature = "terminal-handoff")]
+impl Drop for TerminalHandoffGuard {
+    fn drop(&mut self) {
+        // Best-effort restore; there's nowhere to report a failure to here
+        let _ = crossterm::execute!(
+            io::stdout(),
+            crossterm::terminal::EnterAlternateScreen
+        );
+        let _ = crossterm::terminal::enable_raw_mode();
+    }
+}
+
+/// Does this command token contain a *substitution* placeholder token (see
+/// [EditorBuilder::build_commands])? A literal `{{` escape deliberately
+/// doesn't count - it doesn't need a path to resolve, so it shouldn't opt a
+/// command into per-path fan-out on its own. [substitute_placeholders] still
+/// resolves `{{` wherever it's called, regardless of this check.
+fn has_placeholder(token: &str) -> bool {
+    token.contains("{}")
+        || token.contains("{/}")
+        || token.contains("{//}")
+        || token.contains("{.}")
+        || token.contains("{/.}")
+}
+
+/// Substitute each placeholder token in `token` with the corresponding
+/// component of `path`. A literal `{{` escapes to `{` regardless of whether
+/// `path` is given. If `path` is `None` (no path was supplied to substitute
+/// with), every other token is left as literal text.
+fn substitute_placeholders(token: &str, path: Option<&Path>) -> String {
+    let full = path.map(|path| path.to_string_lossy());
+    let file_name = path
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy());
+    let parent =
+        path.and_then(Path::parent).map(|parent| parent.to_string_lossy());
+    let stem_path = path.map(|path| path.with_extension(""));
+    let stem_path = stem_path.as_deref().map(Path::to_string_lossy);
+    let file_stem = path
+        .and_then(Path::file_stem)
+        .map(|stem| stem.to_string_lossy());
+
+    // No token is a prefix of another, so at most one can match at a given
+    // position and the order here doesn't affect the result
+    const TOKENS: &[&str] = &["{{", "{/.}", "{//}", "{/}", "{.}", "{}"];
+
+    let mut result = String::with_capacity(token.len());
+    let mut rest = token;
+    'outer: while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let candidate = &rest[start..];
+        for &tok in TOKENS {
+            if let Some(remainder) = candidate.strip_prefix(tok) {
+                // If we have no path, leave every token but the escape as
+                // literal text instead of substituting
+                let value = match tok {
+                    "{{" => Some("{"),
+                    "{}" => full.as_deref(),
+                    "{/}" => file_name.as_deref(),
+                    "{//}" => parent.as_deref(),
+                    "{.}" => stem_path.as_deref(),
+                    "{/.}" => file_stem.as_deref(),
+                    _ => unreachable!(),
+                };
+                result.push_str(value.unwrap_or(tok));
+                rest = remainder;
+                continue 'outer;
+            }
+        }
+        // Not a recognized token - keep the brace literal and move past it
+        result.push('{');
+        rest = &candidate[1..];
+    }
+    result.push_str(rest);
+    result
 }
 
 impl<'a> EditorBuilder<'a> {
@@ -141,6 +349,24 @@ impl<'a> EditorBuilder<'a> {
         Self::new().environment().path(path.as_ref()).build()
     }
 
+    /// Shorthand for editing in-memory content with the command set in
+    /// `VISUAL`/`EDITOR`.
+    ///
+    /// ```ignore
+    /// EditorBuilder::edit_content("initial text")
+    /// ```
+    ///
+    /// is equivalent to:
+    ///
+    /// ```ignore
+    /// EditorBuilder::new().environment().build_and_edit("initial text")
+    /// ```
+    pub fn edit_content(
+        initial: &str,
+    ) -> Result<String, EditorBuilderError> {
+        Self::new().environment().build_and_edit(initial)
+    }
+
     /// Add a static string as a source for the command. This is useful for
     /// static defaults, or external sources such as a configuration file.
     /// This accepts an `Option` so you can easily build a chain of sources
@@ -162,6 +388,43 @@ impl<'a> EditorBuilder<'a> {
         self
     }
 
+    /// Add the `GIT_EDITOR` environment variable and git's `core.editor`
+    /// config value (read via `git config --get core.editor`) as sources, in
+    /// that order. A missing `GIT_EDITOR` value, a missing/unset
+    /// `core.editor`, or a non-zero exit from `git` are all treated as
+    /// "unpopulated", so the source chain continues rather than erroring.
+    ///
+    /// Like [environment](Self::environment), this is resolved
+    /// **immediately**, *not* during [build](Self::build). Call this before
+    /// [environment](Self::environment) to get git's real precedence order:
+    /// `GIT_EDITOR` > `core.editor` > `VISUAL` > `EDITOR`.
+    ///
+    /// Requires the `git-config` feature.
+    #[cfg(feature = "git-config")]
+    pub fn git_config(mut self) -> Self {
+        self.command = self
+            .command
+            .or_else(|| env::var("GIT_EDITOR").ok().map(Cow::from))
+            .or_else(|| git_config_core_editor().map(Cow::from));
+        self
+    }
+
+    /// When [run](Self::run) is used, leave raw mode and the alternate
+    /// screen before spawning the editor, then restore them afterward - even
+    /// if the editor fails to spawn or exits with an error. This is for TUI
+    /// applications that need to cede the terminal to a full-screen editor
+    /// process and reclaim it cleanly afterward. Has no effect on
+    /// [build](Self::build)/[build_commands](Self::build_commands); only
+    /// [run](Self::run) (and [build_and_edit](Self::build_and_edit), which
+    /// spawns through it) spawns anything.
+    ///
+    /// Requires the `terminal-handoff` feature.
+    #[cfg(feature = "terminal-handoff")]
+    pub fn terminal_handoff(mut self) -> Self {
+        self.terminal_handoff = true;
+        self
+    }
+
     /// Define the path to be passed as the final argument.
     ///
     /// ## Multiple Calls
@@ -174,15 +437,165 @@ impl<'a> EditorBuilder<'a> {
         self
     }
 
+    /// Set the file extension to use for the temporary file created by
+    /// [edit_content](Self::edit_content)/[build_and_edit](Self::build_and_edit).
+    /// This has no effect on [build](Self::build). Providing the correct
+    /// extension (e.g. `"md"` or `"yml"`) allows editors to apply accurate
+    /// syntax highlighting to the temporary file.
+    pub fn extension(mut self, extension: impl Into<Cow<'a, str>>) -> Self {
+        self.extension = Some(extension.into());
+        self
+    }
+
+    /// Set the working directory for the produced [Command], via
+    /// [Command::current_dir]. Useful when paths should be opened relative
+    /// to a particular root (e.g. a project directory) rather than the
+    /// current process's working directory.
+    pub fn current_dir(mut self, dir: impl Into<Cow<'a, Path>>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Set an environment variable on the produced [Command], via
+    /// [Command::env]. Can be called multiple times to set multiple
+    /// variables.
+    pub fn env(
+        mut self,
+        key: impl Into<Cow<'a, str>>,
+        value: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set multiple environment variables on the produced [Command], via
+    /// [Command::envs]. Equivalent to calling [env](Self::env) once per
+    /// item.
+    pub fn envs<K, V>(mut self, vars: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        for (key, value) in vars {
+            self = self.env(key, value);
+        }
+        self
+    }
+
+    /// Append a fallback source that, at [build](Self::build) time, scans
+    /// `PATH` for a curated list of well-known editors (e.g. `nano`, `vim`,
+    /// `vi`, `emacs`, `micro` on Unix, `notepad.exe` on Windows) and uses the
+    /// first one found. This has the lowest possible priority: it's only
+    /// consulted if every other source (including
+    /// [environment](Self::environment)) is unpopulated. Without this, an
+    /// unpopulated builder fails to build with
+    /// [NoCommand](EditorBuilderError::NoCommand).
+    pub fn fallbacks(mut self) -> Self {
+        self.use_fallbacks = true;
+        self
+    }
+
     /// Search all configured sources (in their order of definition), and parse
     /// the first one that's populated as a shell command. Then use that to
     /// build an executable [Command].
+    ///
+    /// If the command contains a placeholder token (see
+    /// [build_commands](Self::build_commands)) and more than one path was
+    /// given, building would require more than one [Command] (one per path),
+    /// which this method can't return. In that case, this method returns
+    /// [EditorBuilderError::MultipleCommands]; use
+    /// [build_commands](Self::build_commands) instead.
     pub fn build(self) -> Result<Command, EditorBuilderError> {
+        let mut commands = self.build_commands()?;
+        match commands.len() {
+            1 => Ok(commands.remove(0)),
+            n => Err(EditorBuilderError::MultipleCommands(n)),
+        }
+    }
+
+    /// [Build](Self::build) the command, spawn it, and wait for it to exit,
+    /// returning its [ExitStatus]. This is a convenience over building the
+    /// [Command] and spawning it yourself, with two extras:
+    ///
+    /// - If spawning fails, the [io::Error] is wrapped in
+    ///   [EditorBuilderError::Spawn], which names just the program that
+    ///   failed to launch (not the whole argument list), so errors stay
+    ///   readable, e.g. `failed to launch "code": No such file or directory`.
+    /// - If [terminal_handoff](Self::terminal_handoff) was enabled, raw mode
+    ///   and the alternate screen are left before spawning and restored
+    ///   afterward, even if the editor fails to spawn or exits with an
+    ///   error. A failure during that handoff (before the editor is even
+    ///   spawned) is reported as [EditorBuilderError::TerminalHandoff],
+    ///   not [EditorBuilderError::Spawn].
+    pub fn run(self) -> Result<ExitStatus, EditorBuilderError> {
+        #[cfg(feature = "terminal-handoff")]
+        let terminal_handoff = self.terminal_handoff;
+
+        let mut command = self.build()?;
+        let program = command.get_program().to_os_string();
+
+        #[cfg(feature = "terminal-handoff")]
+        let _guard = if terminal_handoff {
+            Some(
+                TerminalHandoffGuard::enter()
+                    .map_err(EditorBuilderError::TerminalHandoff)?,
+            )
+        } else {
+            None
+        };
+
+        command
+            .status()
+            .map_err(|source| EditorBuilderError::Spawn { program, source })
+    }
+
+    /// Like [build](Self::build), but supports commands that expand to
+    /// multiple invocations.
+    ///
+    /// ## Placeholder Tokens
+    ///
+    /// If the command string contains one of the following tokens, each
+    /// occurrence is substituted during this call, instead of appending
+    /// path(s) as trailing arguments:
+    ///
+    /// | Token   | Substitution                        |
+    /// | ------- | ------------------------------------ |
+    /// | `{}`    | Full path                            |
+    /// | `{/}`   | File name (basename)                  |
+    /// | `{//}`  | Parent directory                      |
+    /// | `{.}`   | Path without its extension            |
+    /// | `{/.}`  | File name without its extension       |
+    /// | `{{`    | Literal `{`                           |
+    ///
+    /// Tokens can appear anywhere in the command, including mid-argument
+    /// (e.g. `"--goto {}:1"`). If zero or one path is given, exactly one
+    /// [Command] is returned (with tokens substituted for that single path,
+    /// or left as literal text if no path was given). If more than one path
+    /// is given, one [Command] is returned per path, each with the tokens
+    /// substituted for that path.
+    ///
+    /// If the command contains no *substitution* token (`{}`, `{/}`, `{//}`,
+    /// `{.}`, or `{/.}`), this falls back to the existing behavior of
+    /// appending all paths as trailing arguments to a single command, even
+    /// if the command contains a `{{` escape - `{{` alone doesn't opt a
+    /// command into per-path substitution, it's just resolved to `{` in
+    /// whatever command is produced.
+    pub fn build_commands(
+        self,
+    ) -> Result<Vec<Command>, EditorBuilderError> {
         // Find the first source that has a value. We *don't* validate that the
         // command is non-empty or parses. If something has a value, it's better
         // to use it and give the user an error if it's invalid, than to
         // silently skip past it.
-        let command_str = self.command.ok_or(EditorBuilderError::NoCommand)?;
+        let command_str = match self.command {
+            Some(command) => command,
+            None if self.use_fallbacks => FALLBACK_CANDIDATES
+                .iter()
+                .find(|candidate| find_on_path(candidate))
+                .map(|candidate| Cow::Borrowed(*candidate))
+                .ok_or(EditorBuilderError::NoCommand)?,
+            None => return Err(EditorBuilderError::NoCommand),
+        };
 
         // Parse it as a shell command
         let mut parsed =
@@ -192,13 +605,101 @@ impl<'a> EditorBuilder<'a> {
         // First token is the program name, rest are arguments
         let mut tokens = parsed.drain(..);
         let program = tokens.next().ok_or(EditorBuilderError::EmptyCommand)?;
-        let args = tokens;
+        let args: Vec<String> = tokens.collect();
 
-        let mut command = Command::new(program);
-        command
-            .args(args)
-            .args(self.paths.iter().map(|path| path.as_os_str()));
-        Ok(command)
+        let has_placeholder = has_placeholder(&program)
+            || args.iter().any(|arg| has_placeholder(arg));
+
+        // Apply the working directory and env vars common to every
+        // invocation, regardless of which branch below builds the Command
+        let apply_shared = |command: &mut Command| {
+            if let Some(current_dir) = &self.current_dir {
+                command.current_dir(current_dir);
+            }
+            for (key, value) in &self.envs {
+                command.env(key.as_ref(), value.as_ref());
+            }
+        };
+
+        if !has_placeholder || self.paths.is_empty() {
+            // No substitution token, or no path to substitute with - keep
+            // the original append-at-end behavior, resolving any `{{`
+            // escape first (real placeholder tokens are left as literal
+            // text when there's no path to substitute with, since
+            // `substitute_placeholders` is given `None`)
+            let program = substitute_placeholders(&program, None);
+            let args =
+                args.iter().map(|arg| substitute_placeholders(arg, None));
+            let mut command = Command::new(program);
+            command
+                .args(args)
+                .args(self.paths.iter().map(|path| path.as_os_str()));
+            apply_shared(&mut command);
+            return Ok(vec![command]);
+        }
+
+        self.paths
+            .iter()
+            .map(|path| {
+                let program = substitute_placeholders(&program, Some(path));
+                let args = args
+                    .iter()
+                    .map(|arg| substitute_placeholders(arg, Some(path)));
+                let mut command = Command::new(program);
+                command.args(args);
+                apply_shared(&mut command);
+                Ok(command)
+            })
+            .collect()
+    }
+
+    /// Write `initial` to a new temporary file, open that file in the user's
+    /// editor exactly as [build](Self::build) would (with the temp file path
+    /// appended, or substituted for any placeholder tokens in the command),
+    /// wait for the editor to exit, then read the file's contents
+    /// back. The temporary file is created with a unique name in the system's
+    /// temp directory, and is deleted when this function returns (including
+    /// when the editor fails or panics). If the caller set an
+    /// [extension](Self::extension), it's applied to the temp file name so
+    /// the editor can pick accurate syntax highlighting. If the user makes no
+    /// edits, the returned string is identical to `initial`.
+    pub fn build_and_edit(
+        mut self,
+        initial: &str,
+    ) -> Result<String, EditorBuilderError> {
+        // Any paths passed via `.path()` are irrelevant here; the temp file
+        // is always the one and only path passed to the editor
+        self.paths.clear();
+
+        // Strip any leading dot the caller may have included, so we don't
+        // end up with a doubled-up ".md" -> "..md" suffix. This has to be
+        // bound to a variable (not a temporary) since `Builder` borrows it.
+        let suffix = self
+            .extension
+            .as_deref()
+            .map(|extension| format!(".{}", extension.trim_start_matches('.')));
+        let mut temp_file_builder = TempFileBuilder::new();
+        if let Some(suffix) = &suffix {
+            temp_file_builder.suffix(suffix);
+        }
+        let mut temp_file =
+            temp_file_builder.tempfile().map_err(EditorBuilderError::Io)?;
+        temp_file
+            .write_all(initial.as_bytes())
+            .map_err(EditorBuilderError::Io)?;
+        temp_file.flush().map_err(EditorBuilderError::Io)?;
+        let temp_path = temp_file.path().to_path_buf();
+
+        let status = self.path(temp_path.clone()).run()?;
+        if !status.success() {
+            return Err(EditorBuilderError::EditorFailed(status));
+        }
+
+        let mut edited = String::new();
+        fs::File::open(&temp_path)
+            .and_then(|mut file| file.read_to_string(&mut edited))
+            .map_err(EditorBuilderError::Io)?;
+        Ok(edited)
     }
 }
 
@@ -213,6 +714,34 @@ pub enum EditorBuilderError {
 
     /// Editor command couldn't be parsed in a shell-like format
     ParseError(shellish_parse::ParseError),
+
+    /// An I/O error occurred while creating/writing/reading the temporary
+    /// file used by [build_and_edit](EditorBuilder::build_and_edit)
+    Io(io::Error),
+
+    /// The editor process exited with a non-zero status, so its output can't
+    /// be trusted
+    EditorFailed(std::process::ExitStatus),
+
+    /// The command contains a placeholder token and multiple paths were
+    /// given, so building it required more than one [Command], but
+    /// [build](EditorBuilder::build) can only return one. Use
+    /// [build_commands](EditorBuilder::build_commands) instead.
+    MultipleCommands(usize),
+
+    /// [EditorBuilder::run] failed to spawn the editor process
+    Spawn {
+        /// The program that failed to launch, e.g. `code`
+        program: OsString,
+        /// The underlying error returned by [std::process::Command::status]
+        source: io::Error,
+    },
+
+    /// [EditorBuilder::run] failed to hand off the terminal to the editor
+    /// (e.g. leaving raw mode or the alternate screen), before the editor
+    /// was even spawned
+    #[cfg(feature = "terminal-handoff")]
+    TerminalHandoff(io::Error),
 }
 
 impl Display for EditorBuilderError {
@@ -228,6 +757,26 @@ impl Display for EditorBuilderError {
             EditorBuilderError::ParseError(source) => {
                 write!(f, "Invalid editor command: {source}")
             }
+            EditorBuilderError::Io(source) => {
+                write!(f, "I/O error: {source}")
+            }
+            EditorBuilderError::EditorFailed(status) => {
+                write!(f, "Editor exited with {status}")
+            }
+            EditorBuilderError::MultipleCommands(count) => write!(
+                f,
+                "Command expands to {count} invocations (one per path); \
+                 use `build_commands` instead of `build`"
+            ),
+            EditorBuilderError::Spawn { program, source } => write!(
+                f,
+                "failed to launch {program:?}: {source}",
+                program = program.to_string_lossy()
+            ),
+            #[cfg(feature = "terminal-handoff")]
+            EditorBuilderError::TerminalHandoff(source) => {
+                write!(f, "failed to hand off terminal: {source}")
+            }
         }
     }
 }
@@ -236,8 +785,14 @@ impl Error for EditorBuilderError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
             EditorBuilderError::NoCommand
-            | EditorBuilderError::EmptyCommand => None,
+            | EditorBuilderError::EmptyCommand
+            | EditorBuilderError::EditorFailed(_)
+            | EditorBuilderError::MultipleCommands(_) => None,
             EditorBuilderError::ParseError(source) => Some(source),
+            EditorBuilderError::Io(source) => Some(source),
+            EditorBuilderError::Spawn { source, .. } => Some(source),
+            #[cfg(feature = "terminal-handoff")]
+            EditorBuilderError::TerminalHandoff(source) => Some(source),
         }
     }
 }
@@ -303,6 +858,89 @@ mod tests {
         assert_cmd(builder, "default", &[]);
     }
 
+    /// Test that `GIT_EDITOR` outranks `core.editor`, matching git's own
+    /// precedence
+    #[cfg(feature = "git-config")]
+    #[test]
+    fn git_config_prefers_git_editor_over_core_editor() {
+        let builder = {
+            let _guard = env_lock::lock_env([
+                ("GIT_EDITOR", Some("from-env")),
+                ("GIT_CONFIG_COUNT", Some("1")),
+                ("GIT_CONFIG_KEY_0", Some("core.editor")),
+                ("GIT_CONFIG_VALUE_0", Some("from-config")),
+            ]);
+            EditorBuilder::new().git_config()
+        };
+        assert_cmd(builder, "from-env", &[]);
+    }
+
+    /// Test falling back to `core.editor` when `GIT_EDITOR` isn't set
+    #[cfg(feature = "git-config")]
+    #[test]
+    fn git_config_falls_back_to_core_editor() {
+        let builder = {
+            let _guard = env_lock::lock_env([
+                ("GIT_EDITOR", None::<&str>),
+                ("GIT_CONFIG_COUNT", Some("1")),
+                ("GIT_CONFIG_KEY_0", Some("core.editor")),
+                ("GIT_CONFIG_VALUE_0", Some("from-config")),
+            ]);
+            EditorBuilder::new().git_config()
+        };
+        assert_cmd(builder, "from-config", &[]);
+    }
+
+    /// Test that `git_config` never overrides a command from a
+    /// higher-priority source, even when both `GIT_EDITOR` and
+    /// `core.editor` are populated
+    #[cfg(feature = "git-config")]
+    #[test]
+    fn git_config_does_not_override_existing_command() {
+        let builder = {
+            let _guard = env_lock::lock_env([
+                ("GIT_EDITOR", Some("from-env")),
+                ("GIT_CONFIG_COUNT", Some("1")),
+                ("GIT_CONFIG_KEY_0", Some("core.editor")),
+                ("GIT_CONFIG_VALUE_0", Some("from-config")),
+            ]);
+            EditorBuilder::new().source(Some("priority")).git_config()
+        };
+        assert_cmd(builder, "priority", &[]);
+    }
+
+    /// Test that an unset `GIT_EDITOR` and an unset/non-zero-exit
+    /// `core.editor` lookup leave the command unpopulated, letting the
+    /// source chain continue rather than erroring
+    #[cfg(feature = "git-config")]
+    #[test]
+    fn git_config_unpopulated_falls_through() {
+        // An empty file, pointed to by `GIT_CONFIG_GLOBAL`/`GIT_CONFIG_SYSTEM`
+        // below, so the lookup can't pick up an ambient `core.editor` from
+        // the machine running the test
+        let empty_config = tempfile::NamedTempFile::new().unwrap();
+
+        let builder = {
+            // No pairs set via GIT_CONFIG_COUNT, so `git config --get
+            // core.editor` exits non-zero and git_config() leaves the
+            // command unpopulated
+            let _guard = env_lock::lock_env([
+                ("GIT_EDITOR", None),
+                ("GIT_CONFIG_COUNT", Some("0")),
+                (
+                    "GIT_CONFIG_GLOBAL",
+                    Some(empty_config.path().to_str().unwrap()),
+                ),
+                (
+                    "GIT_CONFIG_SYSTEM",
+                    Some(empty_config.path().to_str().unwrap()),
+                ),
+            ]);
+            EditorBuilder::new().git_config().source(Some("default"))
+        };
+        assert_cmd(builder, "default", &[]);
+    }
+
     /// Test included paths as extra arguments
     #[test]
     fn paths() {
@@ -314,6 +952,58 @@ mod tests {
         assert_cmd(builder, "ed", &["path1", "path2"]);
     }
 
+    /// Test that `.current_dir()`/`.env()` land on the produced `Command` in
+    /// the (no-placeholder) append-at-end branch
+    #[test]
+    fn current_dir_and_env_append_branch() {
+        let command = EditorBuilder::new()
+            .source(Some("ed"))
+            .current_dir(Path::new("/tmp/project"))
+            .env("FOO", "bar")
+            .path(Path::new("file.txt"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            command.get_current_dir(),
+            Some(Path::new("/tmp/project"))
+        );
+        assert_eq!(
+            command.get_envs().collect::<Vec<_>>(),
+            &[(OsStr::new("FOO"), Some(OsStr::new("bar")))]
+        );
+    }
+
+    /// Test that `.current_dir()`/`.envs()` land on every `Command` produced
+    /// by the placeholder-substitution branch
+    #[test]
+    fn current_dir_and_env_placeholder_branch() {
+        let commands = EditorBuilder::new()
+            .source(Some("ed {}"))
+            .current_dir(Path::new("/tmp/project"))
+            .envs([("FOO", "bar"), ("BAZ", "qux")])
+            .path(Path::new("a.txt"))
+            .path(Path::new("b.txt"))
+            .build_commands()
+            .unwrap();
+        for command in &commands {
+            assert_eq!(
+                command.get_current_dir(),
+                Some(Path::new("/tmp/project"))
+            );
+            // `Command::get_envs` doesn't guarantee insertion order, so
+            // sort before comparing
+            let mut envs: Vec<_> = command.get_envs().collect();
+            envs.sort();
+            assert_eq!(
+                envs,
+                &[
+                    (OsStr::new("BAZ"), Some(OsStr::new("qux"))),
+                    (OsStr::new("FOO"), Some(OsStr::new("bar"))),
+                ]
+            );
+        }
+    }
+
     /// Test simple command parsing logic. We'll defer edge cases to
     /// shellish_parse
     #[test]
@@ -358,6 +1048,250 @@ mod tests {
         );
     }
 
+    /// Test every placeholder token substitution in isolation
+    #[test]
+    fn substitute_placeholders_tokens() {
+        let path = Path::new("/home/user/project/file.txt");
+        assert_eq!(
+            substitute_placeholders("{}", Some(path)),
+            "/home/user/project/file.txt"
+        );
+        assert_eq!(
+            substitute_placeholders("{/}", Some(path)),
+            "file.txt"
+        );
+        assert_eq!(
+            substitute_placeholders("{//}", Some(path)),
+            "/home/user/project"
+        );
+        assert_eq!(
+            substitute_placeholders("{.}", Some(path)),
+            "/home/user/project/file"
+        );
+        assert_eq!(substitute_placeholders("{/.}", Some(path)), "file");
+    }
+
+    /// Test a path with no extension - `{.}`/`{/.}` degrade to `{}`/`{/}`
+    #[test]
+    fn substitute_placeholders_no_extension() {
+        let path = Path::new("/tmp/noext");
+        assert_eq!(substitute_placeholders("{.}", Some(path)), "/tmp/noext");
+        assert_eq!(substitute_placeholders("{/.}", Some(path)), "noext");
+    }
+
+    /// Test that a literal `{{` escapes to `{`, both standalone and
+    /// alongside a real token, and regardless of whether a path is given
+    #[test]
+    fn substitute_placeholders_escape() {
+        let path = Path::new("file.txt");
+        assert_eq!(
+            substitute_placeholders("{{literal}}", Some(path)),
+            "{literal}}"
+        );
+        assert_eq!(
+            substitute_placeholders("{{ {} }}", Some(path)),
+            "{ file.txt }}"
+        );
+        assert_eq!(substitute_placeholders("{{literal}}", None), "{literal}}");
+    }
+
+    /// Test substitution mid-argument, e.g. inside what was a quoted arg
+    #[test]
+    fn substitute_placeholders_mid_argument() {
+        let path = Path::new("file.txt");
+        assert_eq!(
+            substitute_placeholders("--goto {}:1", Some(path)),
+            "--goto file.txt:1"
+        );
+    }
+
+    /// Test that a real placeholder token is left as literal text when no
+    /// path is given to substitute with
+    #[test]
+    fn substitute_placeholders_no_path() {
+        assert_eq!(substitute_placeholders("--goto {}:1", None), "--goto {}:1");
+    }
+
+    /// Test that a placeholder command fans out to one Command per path
+    #[test]
+    fn build_commands_multiple_paths() {
+        let commands = EditorBuilder::new()
+            .source(Some("ed --goto {}"))
+            .path(Path::new("file1.txt"))
+            .path(Path::new("file2.txt"))
+            .build_commands()
+            .unwrap();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].get_program(), "ed");
+        assert_eq!(
+            commands[0]
+                .get_args()
+                .filter_map(OsStr::to_str)
+                .collect::<Vec<_>>(),
+            &["--goto", "file1.txt"]
+        );
+        assert_eq!(
+            commands[1]
+                .get_args()
+                .filter_map(OsStr::to_str)
+                .collect::<Vec<_>>(),
+            &["--goto", "file2.txt"]
+        );
+    }
+
+    /// Test that `build` refuses to collapse a multi-path placeholder
+    /// expansion into the single `Command` it's able to return
+    #[test]
+    fn build_multiple_paths_placeholder_errors() {
+        assert_err(
+            EditorBuilder::new()
+                .source(Some("ed --goto {}"))
+                .path(Path::new("file1.txt"))
+                .path(Path::new("file2.txt")),
+            "Command expands to 2 invocations (one per path); use \
+             `build_commands` instead of `build`",
+        );
+    }
+
+    /// Test that a placeholder command with no paths leaves the tokens as
+    /// literal text instead of erroring or substituting nothing
+    #[test]
+    fn build_commands_placeholder_no_paths() {
+        assert_cmd(
+            EditorBuilder::new().source(Some("ed --goto {}")),
+            "ed",
+            &["--goto", "{}"],
+        );
+    }
+
+    /// Test that a command containing only a `{{` escape (no real
+    /// substitution token) is *not* treated as a placeholder command: it
+    /// stays in the append-at-end branch, so every path supplied is kept
+    /// (not dropped) and only one [Command] is produced (not one per path)
+    #[test]
+    fn build_commands_escape_only_is_not_a_placeholder() {
+        let commands = EditorBuilder::new()
+            .source(Some("ed {{x"))
+            .path(Path::new("file1.txt"))
+            .path(Path::new("file2.txt"))
+            .build_commands()
+            .unwrap();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(
+            commands[0]
+                .get_args()
+                .filter_map(OsStr::to_str)
+                .collect::<Vec<_>>(),
+            &["{x", "file1.txt", "file2.txt"]
+        );
+    }
+
+    /// Test that `find_on_path` only resolves files that are both present
+    /// and executable, in a directory we control via `PATH`
+    #[cfg(unix)]
+    #[test]
+    fn find_on_path_respects_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+
+        let exe_path = dir.path().join("my-editor");
+        fs::write(&exe_path, "").unwrap();
+        fs::set_permissions(&exe_path, fs::Permissions::from_mode(0o755))
+            .unwrap();
+
+        let non_exe_path = dir.path().join("not-executable");
+        fs::write(&non_exe_path, "").unwrap();
+        fs::set_permissions(&non_exe_path, fs::Permissions::from_mode(0o644))
+            .unwrap();
+
+        let _guard = env_lock::lock_env([(
+            "PATH",
+            Some(dir.path().to_str().unwrap()),
+        )]);
+
+        assert!(find_on_path("my-editor"));
+        assert!(!find_on_path("not-executable"));
+        assert!(!find_on_path("does-not-exist"));
+    }
+
+    /// Test that `.fallbacks()` surfaces `NoCommand` when nothing on the
+    /// curated candidate list is found on `PATH`. `PATH` is only scanned
+    /// during `build`, so (unlike the other source tests) the lock must
+    /// still be held when we call it.
+    #[test]
+    fn fallbacks_no_candidates_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let _guard = env_lock::lock_env([
+            ("VISUAL", None::<&str>),
+            ("EDITOR", None::<&str>),
+            ("PATH", Some(dir.path().to_str().unwrap())),
+        ]);
+        let builder = EditorBuilder::new().environment().fallbacks();
+        assert_err(
+            builder,
+            "Edit command not defined in any of the listed sources",
+        );
+    }
+
+    /// Test that `build_and_edit` returns the content unchanged when the
+    /// "editor" doesn't touch the temp file
+    #[test]
+    fn build_and_edit_unchanged() {
+        let edited = EditorBuilder::new()
+            .source(Some("true"))
+            .build_and_edit("initial content")
+            .unwrap();
+        assert_eq!(edited, "initial content");
+    }
+
+    /// Test that a non-zero editor exit surfaces as `EditorFailed`, not the
+    /// (possibly stale) temp file content
+    #[test]
+    fn build_and_edit_editor_failed() {
+        let error = EditorBuilder::new()
+            .source(Some("false"))
+            .build_and_edit("initial content")
+            .unwrap_err();
+        assert!(error.to_string().starts_with("Editor exited with"));
+    }
+
+    /// Test that a leading dot on the caller-provided extension doesn't
+    /// produce a doubled-up temp file suffix. The "editor" here appends the
+    /// temp file's own basename to itself, so we can inspect the suffix that
+    /// was actually used.
+    #[test]
+    fn build_and_edit_extension_leading_dot() {
+        let edited = EditorBuilder::new()
+            .source(Some(r#"sh -c 'basename "$1" >> "$1"' --"#))
+            .extension(".md")
+            .build_and_edit("initial content\n")
+            .unwrap();
+        assert!(edited.trim_end().ends_with(".md"));
+        assert!(!edited.contains("..md"));
+    }
+
+    /// Test that a failure to spawn the editor process is wrapped in
+    /// `Spawn`, and that the resulting message names only the program, not
+    /// the full argument list
+    #[test]
+    fn run_spawn_failure_names_program() {
+        let error = EditorBuilder::new()
+            .source(Some("does-not-exist-as-a-command arg1 arg2"))
+            .run()
+            .unwrap_err();
+        assert!(
+            matches!(&error, EditorBuilderError::Spawn { program, .. }
+                if program == "does-not-exist-as-a-command"),
+            "{error:?}"
+        );
+        let message = error.to_string();
+        assert!(message.starts_with(
+            "failed to launch \"does-not-exist-as-a-command\": "
+        ));
+        assert!(!message.contains("arg1"));
+    }
+
     /// Assert that the builder creates the expected command
     fn assert_cmd(
         builder: EditorBuilder,